@@ -0,0 +1,57 @@
+// Unicode to [code page 437](https://en.wikipedia.org/wiki/Code_page_437), the character set of the VGA's built-in font.
+// Besides printable ASCII (0x20 to 0x7e, the same in both), code page 437 has:
+//      - symbols (smileys, card suits, arrows, ...) in place of the ASCII control characters 0x01 to 0x1f and 0x7f, and
+//      - accented letters, Greek letters, maths symbols, and box drawing characters in 0x80 to 0xff.
+// We map the Unicode characters of those glyphs back to their byte, and everything else has no representation.
+
+// The glyphs of 0x01 to 0x1f (0x00 is blank)
+const LOW: &str = "☺☻♥♦♣♠•◘○◙♂♀♪♫☼►◄↕‼¶§▬↨↑↓→←∟↔▲▼";
+// The glyphs of 0x80 to 0xff (0xff is a non-breaking space)
+const HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+/// The code page 437 byte showing `c`, if there is one. Control characters (e.g. `'\n'`) have none.
+pub fn encode(c: char) -> Option<u8> {
+    match c {
+        ' '..='~' => Some(c as u8),
+        '⌂' => Some(0x7f),
+        _ => {
+            if let Some(i) = LOW.chars().position(|g| g == c) {
+                return Some(0x01 + i as u8);
+            }
+            HIGH.chars().position(|g| g == c).map(|i| 0x80 + i as u8)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_are_complete() {
+        assert_eq!(LOW.chars().count(), 0x1f);
+        assert_eq!(HIGH.chars().count(), 0x80);
+    }
+
+    #[test]
+    fn encodes_ascii_symbols_and_letters() {
+        assert_eq!(encode('A'), Some(b'A'));
+        assert_eq!(encode('~'), Some(0x7e));
+        assert_eq!(encode('☺'), Some(0x01));
+        assert_eq!(encode('▼'), Some(0x1f));
+        assert_eq!(encode('Ç'), Some(0x80));
+        assert_eq!(encode('ö'), Some(0x94));
+        assert_eq!(encode('─'), Some(0xc4));
+        assert_eq!(encode('■'), Some(0xfe));
+        assert_eq!(encode('\u{a0}'), Some(0xff));
+    }
+
+    #[test]
+    fn control_and_unknown_characters_have_no_glyph() {
+        assert_eq!(encode('\n'), None);
+        assert_eq!(encode('\t'), None);
+        assert_eq!(encode('\u{7f}'), None);
+        assert_eq!(encode('€'), None);
+        assert_eq!(encode('🦀'), None);
+    }
+}
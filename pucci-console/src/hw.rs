@@ -0,0 +1,239 @@
+// Typed access to hardware registers.
+// There are two ways the CPU talks to devices:
+//      - port-mapped I/O, i.e. the separate 16-bit I/O address space accessed with the `in` and `out` instructions, and
+//      - memory-mapped I/O (MMIO), i.e. device registers (or memory like the VGA text buffer) sitting at physical addresses.
+// Instead of scattering raw pointers and inline assembly across the drivers, we wrap both here so that
+// the unsafe part lives in one place and the register width/direction is part of the type.
+// Nothing but the VGA buffer uses this yet, the port and bitfield helpers are for the PIC, PIT, and keyboard drivers to come.
+
+use core::arch::asm;
+use core::marker::PhantomData;
+use core::ops::{BitAnd, BitOr, Not, Range, Shl, Shr, Sub};
+use core::ptr;
+
+// Values which can be moved through an I/O port, i.e. the operand sizes of `in` and `out`: byte, word, and double word.
+pub trait PortValue: Copy {
+    /// # Safety
+    /// Reading a port can have side effects on the device behind it; the caller must know what is there.
+    unsafe fn read_from_port(port: u16) -> Self;
+    /// # Safety
+    /// Writing a port can have arbitrary side effects on the device behind it; the caller must know what is there.
+    unsafe fn write_to_port(port: u16, value: Self);
+}
+
+impl PortValue for u8 {
+    unsafe fn read_from_port(port: u16) -> u8 {
+        let value: u8;
+        asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack, preserves_flags));
+        value
+    }
+    unsafe fn write_to_port(port: u16, value: u8) {
+        asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack, preserves_flags));
+    }
+}
+
+impl PortValue for u16 {
+    unsafe fn read_from_port(port: u16) -> u16 {
+        let value: u16;
+        asm!("in ax, dx", out("ax") value, in("dx") port, options(nomem, nostack, preserves_flags));
+        value
+    }
+    unsafe fn write_to_port(port: u16, value: u16) {
+        asm!("out dx, ax", in("dx") port, in("ax") value, options(nomem, nostack, preserves_flags));
+    }
+}
+
+impl PortValue for u32 {
+    unsafe fn read_from_port(port: u16) -> u32 {
+        let value: u32;
+        asm!("in eax, dx", out("eax") value, in("dx") port, options(nomem, nostack, preserves_flags));
+        value
+    }
+    unsafe fn write_to_port(port: u16, value: u32) {
+        asm!("out dx, eax", in("dx") port, in("eax") value, options(nomem, nostack, preserves_flags));
+    }
+}
+
+// An I/O port of a given width.
+// Creating a port is safe, but reading and writing are not because
+// a port access can have arbitrary side effects on the device (e.g. acknowledging an interrupt).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Port<T: PortValue> {
+    port: u16,
+    phantom: PhantomData<T>,
+}
+
+impl<T: PortValue> Port<T> {
+    pub const fn new(port: u16) -> Port<T> {
+        Port {
+            port,
+            phantom: PhantomData,
+        }
+    }
+    /// # Safety
+    /// See `PortValue::read_from_port`.
+    pub unsafe fn read(&mut self) -> T {
+        T::read_from_port(self.port)
+    }
+    /// # Safety
+    /// See `PortValue::write_to_port`.
+    pub unsafe fn write(&mut self, value: T) {
+        T::write_to_port(self.port, value)
+    }
+}
+
+// MMIO registers.
+// These are `repr(transparent)` so that they can be laid directly over device memory (e.g. as array elements),
+// and every access goes through `read_volatile`/`write_volatile` so that the compiler never merges or removes them.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ReadWrite<T: Copy>(T);
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ReadOnly<T: Copy>(T);
+
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct WriteOnly<T: Copy>(T);
+
+impl<T: Copy> ReadWrite<T> {
+    // For registers living in ordinary memory rather than over a device, e.g. in the tests below
+    pub const fn new(value: T) -> ReadWrite<T> {
+        ReadWrite(value)
+    }
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(&self.0) }
+    }
+    pub fn write(&mut self, value: T) {
+        unsafe { ptr::write_volatile(&mut self.0, value) }
+    }
+    // Read-modify-write in one call, e.g. `reg.update(|v| v | ENABLE)`
+    pub fn update<F: FnOnce(T) -> T>(&mut self, f: F) {
+        let value = self.read();
+        self.write(f(value));
+    }
+}
+
+impl<T: Copy> ReadOnly<T> {
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(&self.0) }
+    }
+}
+
+impl<T: Copy> WriteOnly<T> {
+    pub fn write(&mut self, value: T) {
+        unsafe { ptr::write_volatile(&mut self.0, value) }
+    }
+}
+
+// Bitfield accessors for integer registers.
+// Bit ranges are given as `lo..hi` (hi exclusive), e.g. the foreground colour of a VGA attribute byte is `0..4`.
+pub trait Bits:
+    Copy
+    + PartialEq
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + Sub<Output = Self>
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+    // All ones in the lowest `width` bits
+    fn mask(width: u32) -> Self {
+        if width >= Self::BITS {
+            !Self::ZERO
+        } else {
+            (Self::ONE << width) - Self::ONE
+        }
+    }
+    fn get_bits(self, range: Range<u32>) -> Self {
+        assert!(range.start < range.end && range.end <= Self::BITS);
+        (self >> range.start) & Self::mask(range.end - range.start)
+    }
+    fn set_bits(self, range: Range<u32>, value: Self) -> Self {
+        assert!(range.start < range.end && range.end <= Self::BITS);
+        let mask = Self::mask(range.end - range.start);
+        (self & !(mask << range.start)) | ((value & mask) << range.start)
+    }
+    fn get_bit(self, bit: u32) -> bool {
+        self.get_bits(bit..bit + 1) != Self::ZERO
+    }
+    fn set_bit(self, bit: u32, value: bool) -> Self {
+        self.set_bits(bit..bit + 1, if value { Self::ONE } else { Self::ZERO })
+    }
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {
+        $(
+            impl Bits for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+            }
+        )*
+    };
+}
+impl_bits!(u8, u16, u32, u64);
+
+impl<T: Copy + Bits> ReadWrite<T> {
+    pub fn read_bits(&self, range: Range<u32>) -> T {
+        self.read().get_bits(range)
+    }
+    pub fn write_bits(&mut self, range: Range<u32>, value: T) {
+        self.update(|v| v.set_bits(range, value));
+    }
+}
+
+impl<T: Copy + Bits> ReadOnly<T> {
+    pub fn read_bits(&self, range: Range<u32>) -> T {
+        self.read().get_bits(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_covers_the_full_width() {
+        assert_eq!(u8::mask(u8::BITS), 0xff);
+        assert_eq!(u64::mask(u64::BITS), u64::MAX);
+        assert_eq!(u16::mask(4), 0x000f);
+        assert_eq!(u32::mask(0), 0);
+    }
+
+    #[test]
+    fn set_bits_keeps_the_neighbouring_bits() {
+        let value: u16 = 0b1010_1010_1010_1010;
+        assert_eq!(value.set_bits(4..8, 0b0101), 0b1010_1010_0101_1010);
+        // Bits of `value` beyond the range are dropped
+        assert_eq!(value.set_bits(4..8, 0xff_f0), 0b1010_1010_0000_1010);
+        assert_eq!(value.get_bits(4..12), 0b1010_1010);
+    }
+
+    #[test]
+    fn top_bit_of_u64() {
+        let value = 0u64.set_bit(63, true);
+        assert_eq!(value, 1 << 63);
+        assert!(value.get_bit(63));
+        assert!(!value.get_bit(62));
+        assert_eq!(value.get_bits(32..64), 0x8000_0000);
+        assert_eq!(value.set_bit(63, false), 0);
+    }
+
+    #[test]
+    fn write_bits_through_a_register() {
+        // The attribute byte of a VGA cell: background 0x1 (blue), foreground 0xe (yellow)
+        let mut register = ReadWrite::new(0x1eu8);
+        register.write_bits(0..4, 0x4);
+        assert_eq!(register.read(), 0x14);
+        assert_eq!(register.read_bits(4..8), 0x1);
+        register.update(|v| v.set_bit(7, true));
+        assert_eq!(register.read(), 0x94);
+    }
+}
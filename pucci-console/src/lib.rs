@@ -0,0 +1,14 @@
+// Console output for pucci, split out of the kernel binary so that the formatting and scrolling logic
+// can be unit tested on the host without booting QEMU:
+// `cargo test-console` (see the alias in .cargo/config.toml)
+// The crate is `no_std` everywhere except in its own tests, where we borrow std for `Box`, `String`, and the test harness.
+#![cfg_attr(not(test), no_std)]
+
+pub mod cp437;
+pub mod hw;
+pub mod panic_screen;
+pub mod shadow;
+pub mod vga_buffer;
+
+#[cfg(test)]
+mod mock;
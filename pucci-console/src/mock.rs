@@ -0,0 +1,47 @@
+// A mock screen for the host unit tests, i.e. a TextBuffer in ordinary memory instead of 0xb8000.
+// It can be smaller than the real one to keep the tests short.
+use crate::vga_buffer::{Colour, ColourCode, ScreenChar, TextBuffer};
+
+pub struct MockBuffer {
+    pub chars: Vec<Vec<ScreenChar>>,
+    // Number of write_char calls, i.e. how many (volatile) writes the real screen would have seen
+    pub writes: usize,
+}
+
+impl MockBuffer {
+    pub fn new(width: usize, height: usize) -> MockBuffer {
+        let blank = ScreenChar {
+            ascii_character: b' ',
+            colour_code: ColourCode::new(Colour::Black, Colour::Black),
+        };
+        MockBuffer {
+            chars: vec![vec![blank; width]; height],
+            writes: 0,
+        }
+    }
+    // The characters of a row as a string, without the trailing blanks
+    pub fn row_text(&self, row: usize) -> String {
+        self.chars[row]
+            .iter()
+            .map(|c| c.ascii_character as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+}
+
+impl TextBuffer for MockBuffer {
+    fn width(&self) -> usize {
+        self.chars[0].len()
+    }
+    fn height(&self) -> usize {
+        self.chars.len()
+    }
+    fn read_char(&self, row: usize, col: usize) -> ScreenChar {
+        self.chars[row][col]
+    }
+    fn write_char(&mut self, row: usize, col: usize, character: ScreenChar) {
+        self.chars[row][col] = character;
+        self.writes += 1;
+    }
+}
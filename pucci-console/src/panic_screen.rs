@@ -0,0 +1,257 @@
+// Full-screen panic display ("blue screen").
+// When the kernel panics we take over the whole text buffer and draw, from top to bottom:
+//      - a red banner,
+//      - a short error code derived from the panic location (easy to read out, photograph, or put in a QR code),
+//      - the location and the panic message (wrapped over as many rows as it needs, up to the register dump), and
+//      - a snapshot of a few CPU registers taken in the panic handler, just above a final "System halted." row.
+// Backtraces need frame pointers which we don't compile with yet, so there are none for now.
+use crate::cp437;
+use crate::vga_buffer::{Colour, ColourCode, ScreenChar, TextBuffer};
+use core::arch::asm;
+use core::fmt::{self, Write};
+
+const BANNER: &str = "*** PUCCI KERNEL PANIC ***";
+
+/// CPU registers captured at the start of the panic handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub rsp: u64,
+    pub rbp: u64,
+    pub rflags: u64,
+    pub cr0: u64,
+    pub cr2: u64,
+    pub cr3: u64,
+    pub cr4: u64,
+}
+
+impl Registers {
+    /// Reads the registers of the calling function.
+    ///
+    /// # Safety
+    /// Must run in ring 0: reading the control registers anywhere else raises a general protection fault
+    /// (e.g. in a host test, where it kills the test process).
+    // Inlined so that rsp and rbp are those of the panic handler rather than of this function
+    #[inline(always)]
+    pub unsafe fn capture() -> Registers {
+        let (rsp, rbp, rflags, cr0, cr2, cr3, cr4): (u64, u64, u64, u64, u64, u64, u64);
+        asm!("mov {}, rsp", out(reg) rsp, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+        asm!("pushfq; pop {}", out(reg) rflags, options(nomem, preserves_flags));
+        asm!("mov {}, cr0", out(reg) cr0, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags));
+        asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags));
+        Registers { rsp, rbp, rflags, cr0, cr2, cr3, cr4 }
+    }
+    fn named(&self) -> [(&'static str, u64); 7] {
+        [
+            ("RSP", self.rsp),
+            ("RBP", self.rbp),
+            ("RFLAGS", self.rflags),
+            ("CR0", self.cr0),
+            ("CR2", self.cr2),
+            ("CR3", self.cr3),
+            ("CR4", self.cr4),
+        ]
+    }
+}
+
+/// Everything shown on the panic screen.
+pub struct PanicReport<'a> {
+    pub message: &'a dyn fmt::Display,
+    // (file, line, column)
+    pub location: Option<(&'a str, u32, u32)>,
+    pub registers: Registers,
+}
+
+/// A compact code identifying where we panicked: the 32-bit FNV-1a hash of "file:line:column".
+// The same panic site always gives the same code, so it can be matched against bug reports without the full message.
+pub fn error_code(file: &str, line: u32, column: u32) -> u32 {
+    struct Fnv1a(u32);
+    impl Write for Fnv1a {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for byte in s.bytes() {
+                self.0 ^= byte as u32;
+                self.0 = self.0.wrapping_mul(0x0100_0193);
+            }
+            Ok(())
+        }
+    }
+    let mut hash = Fnv1a(0x811c_9dc5);
+    let _ = write!(hash, "{}:{}:{}", file, line, column);
+    hash.0
+}
+
+// Writes text left to right and top to bottom within rows [row, bottom), wrapping at the right edge.
+// Unlike the Writer it never scrolls: whatever doesn't fit is dropped, so it can't push the banner off the screen.
+struct Cursor<'b, B: TextBuffer> {
+    buffer: &'b mut B,
+    row: usize,
+    col: usize,
+    bottom: usize,
+    colour_code: ColourCode,
+}
+
+impl<'b, B: TextBuffer> Cursor<'b, B> {
+    fn new_line(&mut self) {
+        self.row += 1;
+        self.col = 0;
+    }
+}
+
+impl<'b, B: TextBuffer> Write for Cursor<'b, B> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.new_line();
+                continue;
+            }
+            if self.col >= self.buffer.width() {
+                self.new_line();
+            }
+            if self.row >= self.bottom {
+                break;
+            }
+            let ascii_character = cp437::encode(c).unwrap_or(0xfe);
+            self.buffer.write_char(self.row, self.col, ScreenChar {
+                ascii_character,
+                colour_code: self.colour_code,
+            });
+            self.col += 1;
+        }
+        Ok(())
+    }
+}
+
+fn fill_row<B: TextBuffer>(buffer: &mut B, row: usize, colour_code: ColourCode) {
+    for col in 0..buffer.width() {
+        buffer.write_char(row, col, ScreenChar {
+            ascii_character: b' ',
+            colour_code,
+        });
+    }
+}
+
+/// Clears the whole buffer and draws the panic report on it.
+pub fn draw<B: TextBuffer>(buffer: &mut B, report: &PanicReport) {
+    let text = ColourCode::new(Colour::White, Colour::Blue);
+    let banner = ColourCode::new(Colour::White, Colour::Red);
+    let height = buffer.height();
+    let width = buffer.width();
+    for row in 0..height {
+        fill_row(buffer, row, if row == 0 { banner } else { text });
+    }
+    // Registers go just above the last row, as many per row as fit ("RFLAGS=0x0000000000000000" plus a gap is 27 characters)
+    let registers = report.registers.named();
+    let per_row = core::cmp::max(width / 27, 1);
+    let register_rows = registers.len().div_ceil(per_row);
+    let registers_top = height.saturating_sub(register_rows + 1);
+
+    let mut cursor = Cursor {
+        buffer,
+        row: 0,
+        col: width.saturating_sub(BANNER.len()) / 2,
+        bottom: 1,
+        colour_code: banner,
+    };
+    let _ = cursor.write_str(BANNER);
+
+    cursor.row = 2;
+    cursor.col = 0;
+    cursor.bottom = registers_top.saturating_sub(1);
+    cursor.colour_code = text;
+    let _ = match report.location {
+        Some((file, line, column)) => write!(
+            cursor,
+            "Error code: PUCCI-{:08X}\nLocation:   {}:{}:{}\n\n",
+            error_code(file, line, column),
+            file,
+            line,
+            column
+        ),
+        None => write!(cursor, "Error code: PUCCI-00000000\nLocation:   unknown\n\n"),
+    };
+    let _ = write!(cursor, "{}", report.message);
+
+    cursor.bottom = height;
+    for (i, (name, value)) in registers.iter().enumerate() {
+        cursor.row = registers_top + i / per_row;
+        cursor.col = (i % per_row) * 27;
+        let _ = write!(cursor, "{:>6}={:#018x}", name, value);
+    }
+    cursor.row = height - 1;
+    cursor.col = 0;
+    let _ = cursor.write_str("System halted.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBuffer;
+
+    fn registers() -> Registers {
+        Registers {
+            rsp: 0x1000,
+            rbp: 0x2000,
+            rflags: 0x46,
+            cr0: 0x8001_0033,
+            cr2: 0xdead_beef,
+            cr3: 0x3000,
+            cr4: 0x20,
+        }
+    }
+
+    #[test]
+    fn error_code_is_fnv1a_of_location() {
+        assert_ne!(error_code("src/main.rs", 10, 5), error_code("src/main.rs", 11, 5));
+        let mut hash = 0x811c_9dc5u32;
+        for byte in b"src/main.rs:10:5" {
+            hash = (hash ^ *byte as u32).wrapping_mul(0x0100_0193);
+        }
+        assert_eq!(error_code("src/main.rs", 10, 5), hash);
+    }
+
+    #[test]
+    fn draws_banner_message_and_registers() {
+        let mut buffer = MockBuffer::new(80, 25);
+        let message = "attempt to divide by zero";
+        draw(&mut buffer, &PanicReport {
+            message: &message,
+            location: Some(("src/main.rs", 10, 5)),
+            registers: registers(),
+        });
+        assert_eq!(buffer.row_text(0).trim_start(), BANNER);
+        assert_eq!(buffer.chars[0][0].colour_code, ColourCode::new(Colour::White, Colour::Red));
+        assert_eq!(buffer.chars[1][0].colour_code, ColourCode::new(Colour::White, Colour::Blue));
+        assert_eq!(
+            buffer.row_text(2),
+            format!("Error code: PUCCI-{:08X}", error_code("src/main.rs", 10, 5))
+        );
+        assert_eq!(buffer.row_text(3), "Location:   src/main.rs:10:5");
+        assert_eq!(buffer.row_text(5), message);
+        // 7 registers, 2 per row on an 80 column screen, on the rows above the last one
+        assert_eq!(buffer.row_text(19), "");
+        assert_eq!(buffer.row_text(20).trim_start(), "RSP=0x0000000000001000     RBP=0x0000000000002000");
+        assert!(buffer.row_text(21).contains("CR0=0x0000000080010033"));
+        assert!(buffer.row_text(22).contains("CR2=0x00000000deadbeef"));
+        assert_eq!(buffer.row_text(23).trim_start(), "CR4=0x0000000000000020");
+        assert_eq!(buffer.row_text(24), "System halted.");
+    }
+
+    #[test]
+    fn long_messages_wrap_but_never_cover_the_registers() {
+        let mut buffer = MockBuffer::new(30, 16);
+        let message = "x".repeat(30 * 16);
+        draw(&mut buffer, &PanicReport {
+            message: &message,
+            location: None,
+            registers: registers(),
+        });
+        // 7 registers, 1 per row on a 30 column screen, so they start at row 8 and the message stops before row 7
+        assert_eq!(buffer.row_text(3), "Location:   unknown");
+        assert_eq!(buffer.row_text(5), "x".repeat(30));
+        assert_eq!(buffer.row_text(6), "x".repeat(30));
+        assert_eq!(buffer.row_text(7), "");
+        assert!(buffer.row_text(8).starts_with("   RSP="));
+    }
+}
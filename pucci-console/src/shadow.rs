@@ -0,0 +1,146 @@
+// A shadow copy of a text buffer in ordinary memory.
+// Every write to VGA memory is a volatile (uncached, comparatively slow) access, and reading it back is even slower,
+// which makes scrolling through the real buffer (read and rewrite every cell) expensive.
+// The ShadowBuffer takes all reads and writes in RAM instead and remembers, per row, which columns changed.
+// `flush` then copies only those cells to the real buffer, so e.g. a scroll costs one pass of writes
+// over the cells which actually differ, and a `write!` with many arguments is copied out once at the end.
+use crate::vga_buffer::{ScreenChar, TextBuffer, BUFFER_HEIGHT, BUFFER_WIDTH};
+
+// The largest screen we shadow, i.e. the VGA text buffer
+const MAX_WIDTH: usize = BUFFER_WIDTH;
+const MAX_HEIGHT: usize = BUFFER_HEIGHT;
+
+/// A `TextBuffer` caching another one in memory and writing only the changed cells back on `flush`.
+pub struct ShadowBuffer<B: TextBuffer> {
+    inner: B,
+    chars: [[ScreenChar; MAX_WIDTH]; MAX_HEIGHT],
+    // Changed columns per row as a [start, end) range, empty (start >= end) if the row is clean
+    dirty: [(usize, usize); MAX_HEIGHT],
+}
+
+impl<B: TextBuffer> ShadowBuffer<B> {
+    /// Wraps `inner`, starting from a copy of what it currently shows.
+    ///
+    /// # Panics
+    /// If `inner` is larger than the VGA text buffer (80x25), as the copy is a fixed-size array.
+    pub fn new(inner: B) -> ShadowBuffer<B> {
+        assert!(
+            inner.width() <= MAX_WIDTH && inner.height() <= MAX_HEIGHT,
+            "a ShadowBuffer holds at most {}x{} characters",
+            MAX_WIDTH,
+            MAX_HEIGHT
+        );
+        let mut shadow = ShadowBuffer {
+            chars: [[inner.read_char(0, 0); MAX_WIDTH]; MAX_HEIGHT],
+            dirty: [(MAX_WIDTH, 0); MAX_HEIGHT],
+            inner,
+        };
+        for row in 0..shadow.inner.height() {
+            for col in 0..shadow.inner.width() {
+                shadow.chars[row][col] = shadow.inner.read_char(row, col);
+            }
+        }
+        shadow
+    }
+}
+
+impl<B: TextBuffer> TextBuffer for ShadowBuffer<B> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+    fn height(&self) -> usize {
+        self.inner.height()
+    }
+    fn read_char(&self, row: usize, col: usize) -> ScreenChar {
+        self.chars[row][col]
+    }
+    // Writing what is already there (e.g. blanks over blanks when scrolling) doesn't make the cell dirty
+    fn write_char(&mut self, row: usize, col: usize, character: ScreenChar) {
+        if self.chars[row][col] == character {
+            return;
+        }
+        self.chars[row][col] = character;
+        let (start, end) = self.dirty[row];
+        self.dirty[row] = (start.min(col), end.max(col + 1));
+    }
+    fn flush(&mut self) {
+        for row in 0..self.inner.height() {
+            let (start, end) = self.dirty[row];
+            for col in start..end {
+                self.inner.write_char(row, col, self.chars[row][col]);
+            }
+            self.dirty[row] = (MAX_WIDTH, 0);
+        }
+        self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBuffer;
+    use crate::vga_buffer::{Colour, ColourCode, Writer};
+    use core::fmt::Write;
+
+    // The same output through a shadow and straight into a buffer must look the same
+    #[test]
+    fn shadowed_output_matches_direct_output() {
+        let text = "first line\nsecond line which is long enough to wrap around the right edge\nthird";
+        let mut direct = MockBuffer::new(30, 4);
+        write!(Writer::new(Colour::Cyan, Colour::Black, &mut direct), "{}", text).unwrap();
+        let mut shadowed = MockBuffer::new(30, 4);
+        write!(Writer::new(Colour::Cyan, Colour::Black, ShadowBuffer::new(&mut shadowed)), "{}", text).unwrap();
+        assert_eq!(shadowed.chars, direct.chars);
+    }
+
+    #[test]
+    fn nothing_reaches_the_screen_before_flush() {
+        let mut screen = MockBuffer::new(10, 2);
+        let mut shadow = ShadowBuffer::new(&mut screen);
+        let character = ScreenChar {
+            ascii_character: b'x',
+            colour_code: ColourCode::new(Colour::White, Colour::Black),
+        };
+        shadow.write_char(1, 3, character);
+        assert_eq!(shadow.read_char(1, 3), character);
+        assert_eq!(screen.writes, 0);
+    }
+
+    // Only cells which changed are written out, once per `write!` rather than once per formatted piece
+    #[test]
+    fn formatted_output_writes_each_changed_cell_once() {
+        let mut screen = MockBuffer::new(80, 25);
+        let mut writer = Writer::new(Colour::White, Colour::Black, ShadowBuffer::new(&mut screen));
+        write!(writer, "{} + {} = {}", 1, 2, 3).unwrap();
+        // Mock cells start as black-on-black blanks, so the spaces in between change colour and count too
+        assert_eq!(screen.writes, "1 + 2 = 3".len());
+        assert_eq!(screen.row_text(24), "1 + 2 = 3");
+    }
+
+    #[test]
+    fn scrolling_writes_only_changed_cells() {
+        let mut screen = MockBuffer::new(80, 25);
+        let mut writer = Writer::new(Colour::White, Colour::Black, ShadowBuffer::new(&mut screen));
+        writer.write_string("ab");
+        writer.write_string("\n");
+        // "ab" on the last row, then "ab" moves up a row (2 writes) and the last row is cleared in
+        // the writer's colour (80 writes, as the mock's blanks are black-on-black)
+        assert_eq!(screen.writes, 2 + 2 + 80);
+        assert_eq!(screen.row_text(23), "ab");
+        assert_eq!(screen.row_text(24), "");
+    }
+
+    #[test]
+    fn starts_from_what_is_on_screen() {
+        let mut screen = MockBuffer::new(10, 2);
+        write!(Writer::new(Colour::White, Colour::Black, &mut screen), "boot").unwrap();
+        let shadow = ShadowBuffer::new(&mut screen);
+        assert_eq!(shadow.read_char(1, 0).ascii_character, b'b');
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 80x25")]
+    fn buffers_larger_than_the_screen_are_rejected() {
+        ShadowBuffer::new(MockBuffer::new(MAX_WIDTH, MAX_HEIGHT + 1));
+    }
+}
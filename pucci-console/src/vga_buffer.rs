@@ -0,0 +1,587 @@
+// We store the colours as u8 although u4 is enough but Rust does not have u4
+// We will fit all the colour propeties in u8.
+// To do this we will perform a u8 shift such that:
+//      - the foreground colour occupies the first 4 bits, and 
+//      - the background colour will be shifted to to the last 4 bits via (brackground as u8) << 4 | (foreground as u8)
+// #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Colour {
+    Black = 0,
+    Blue = 1,
+    Green = 2,
+    Cyan = 3,
+    Red = 4,
+    Magenta = 5,
+    Brown = 6,
+    LightGray = 7,
+    DarkGray = 8,
+    LightBlue = 9,
+    LightGreen = 10,
+    LightCyan = 11,
+    LightRed = 12,
+    Pink = 13,
+    Yellow = 14,
+    White = 15,
+}
+
+/// A VGA attribute byte, i.e. a foreground and a background colour.
+// We will store the full colour codes (foreground and background colours) in u8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ColourCode(u8);
+
+impl ColourCode {
+    // Here we fit both foreground and background colours into 8 bits
+    //      - We shift the background colour up to the last 4 bits,
+    //      - and perform a bitwise or so that the foreground colour occupies the first 4 bits.
+    pub fn new(foreground: Colour, background: Colour) -> ColourCode {
+        ColourCode((background as u8) << 4 | (foreground as u8))
+    }
+}
+
+// Characters to be printed on-screen will be u8 (ASCII, i.e. [code page 47](https://en.wikipedia.org/wiki/Code_page_437))
+// We need to have the struct elements sorted as is and since Rust doesn't care of the order we use C's sorted struct layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct ScreenChar {
+    pub ascii_character: u8,
+    pub colour_code: ColourCode,
+}
+
+/// A grid of screen characters the `Writer` draws into.
+// The real implementation is the VGA text buffer at 0xb8000 (`Buffer` below), but abstracting over it lets us
+// run the writer against plain memory, e.g. a mock in the host unit tests.
+pub trait TextBuffer {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn read_char(&self, row: usize, col: usize) -> ScreenChar;
+    fn write_char(&mut self, row: usize, col: usize, character: ScreenChar);
+    // Make everything written so far visible. Buffers writing straight to the screen have nothing to do,
+    // buffered ones (e.g. `shadow::ShadowBuffer`) copy out what changed.
+    fn flush(&mut self) {}
+}
+
+// So that a Writer can own either a buffer or a (`'static`) reference to one, e.g. the VGA buffer
+impl<B: TextBuffer + ?Sized> TextBuffer for &mut B {
+    fn width(&self) -> usize {
+        (**self).width()
+    }
+    fn height(&self) -> usize {
+        (**self).height()
+    }
+    fn read_char(&self, row: usize, col: usize) -> ScreenChar {
+        (**self).read_char(row, col)
+    }
+    fn write_char(&mut self, row: usize, col: usize, character: ScreenChar) {
+        (**self).write_char(row, col, character)
+    }
+    fn flush(&mut self) {
+        (**self).flush()
+    }
+}
+
+// Struct for the text buffer with the same type as its underlying element, i.e.
+// two-dimensional array containing the C-style sorted element struct, ScreenChar.
+// The first dimension refers to the row position and and second refers to the column position.
+pub(crate) const BUFFER_HEIGHT: usize = 25;
+pub(crate) const BUFFER_WIDTH: usize = 80;
+// #[repr(transparent)]
+// struct Buffer {
+//     chars: [[ScreenChar; BUFFER_WIDTH]; BUFFER_HEIGHT],
+// }
+// We revise the Buffer using volatile MMIO registers (see `hw::ReadWrite`; this used to be the volatile crate's Volatile)
+// The Buffer is public so that callers can hold it, but its cells are not: all writes go through a Writer.
+use crate::cp437;
+use crate::hw::ReadWrite;
+pub struct Buffer {
+    chars: [[ReadWrite<ScreenChar>; BUFFER_WIDTH]; BUFFER_HEIGHT],
+}
+
+// Compile-time layout checks for the structs we lay over VGA memory.
+// If a `repr` attribute is dropped or a field is added, the build fails here instead of the screen showing garbage.
+//      - ColourCode is exactly one byte (the attribute byte),
+//      - ScreenChar is the character byte followed by the attribute byte (2 bytes, byte-aligned),
+//      - and the Buffer covers exactly the 80x25 cells starting at 0xb8000 (4000 bytes), i.e. ReadWrite adds no padding.
+use core::mem::{align_of, size_of};
+const _: () = assert!(size_of::<ColourCode>() == 1);
+const _: () = assert!(size_of::<ScreenChar>() == 2);
+const _: () = assert!(align_of::<ScreenChar>() == 1);
+const _: () = assert!(core::mem::offset_of!(ScreenChar, colour_code) == 1);
+const _: () = assert!(size_of::<ReadWrite<ScreenChar>>() == size_of::<ScreenChar>());
+const _: () = assert!(size_of::<Buffer>() == BUFFER_WIDTH * BUFFER_HEIGHT * 2);
+
+// Every access to the VGA buffer is volatile (see `put_byte` below for why)
+impl TextBuffer for Buffer {
+    fn width(&self) -> usize {
+        BUFFER_WIDTH
+    }
+    fn height(&self) -> usize {
+        BUFFER_HEIGHT
+    }
+    fn read_char(&self, row: usize, col: usize) -> ScreenChar {
+        self.chars[row][col].read()
+    }
+    fn write_char(&mut self, row: usize, col: usize, character: ScreenChar) {
+        self.chars[row][col].write(character)
+    }
+}
+
+// The VGA text buffer lives at the fixed physical address 0xb8000 (identity-mapped by the bootloader).
+// Handing out more than one `&'static mut` to it would be undefined behaviour, so we only give it out once.
+use core::sync::atomic::{AtomicBool, Ordering};
+static VGA_BUFFER_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Returns the VGA text buffer at 0xb8000 the first time it is called, and `None` afterwards.
+///
+/// # Safety
+/// 0xb8000 must be identity-mapped VGA text memory, as it is in the kernel booted by the bootloader.
+/// Anywhere else (e.g. in a host test) the address is not ours and writing to it crashes, or worse.
+pub unsafe fn take_vga_buffer() -> Option<&'static mut Buffer> {
+    if VGA_BUFFER_TAKEN.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+    Some(&mut *(0xb8000 as *mut Buffer))
+}
+
+/// Returns the VGA text buffer at 0xb8000 whether or not it has already been taken.
+///
+/// # Safety
+/// Whoever took the buffer before must never write to it again, e.g. because we are in the panic handler and
+/// the code which was printing will never resume. Otherwise we would have two live `&mut` to the same memory.
+pub unsafe fn steal_vga_buffer() -> &'static mut Buffer {
+    VGA_BUFFER_TAKEN.store(true, Ordering::SeqCst);
+    &mut *(0xb8000 as *mut Buffer)
+}
+
+/// What the Writer does with text running past the right edge of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Continue on the next line, splitting words anywhere (the default)
+    Character,
+    /// Drop the rest of the line, marking the last column with » (code page 437 has no …)
+    Truncate,
+    /// Continue on the next line, taking along the start of a word which doesn't fit
+    Word,
+}
+
+/// Writes text into a `TextBuffer`, always on the bottom row, scrolling everything up by one row on newlines.
+// For the screen, B is `&'static mut Buffer` so that our reference to the screen buffer is valid for the entire program,
+// or better a `ShadowBuffer` around it so that formatted output is written to VGA memory in one go.
+// The public methods below flush the buffer once when they are done, the private `put_*`/`scroll` ones don't.
+pub struct Writer<B: TextBuffer> {
+    column_position: usize,
+    colour_code: ColourCode,
+    wrap_mode: WrapMode,
+    tab_width: usize,
+    destructive_backspace: bool,
+    buffer: B,
+}
+
+impl<B: TextBuffer> Writer<B> {
+    /// Creates a writer printing in the given colours, starting at the beginning of the bottom row.
+    pub fn new(foreground: Colour, background: Colour, buffer: B) -> Writer<B> {
+        Writer {
+            column_position: 0,
+            colour_code: ColourCode::new(foreground, background),
+            wrap_mode: WrapMode::Character,
+            tab_width: 8,
+            destructive_backspace: false,
+            buffer,
+        }
+    }
+    /// Puts tab stops every `width` columns (8 by default).
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.tab_width = width.max(1);
+    }
+    /// Whether backspace also erases the character it moves back onto (off by default, i.e. like a terminal).
+    pub fn set_destructive_backspace(&mut self, erase: bool) {
+        self.destructive_backspace = erase;
+    }
+    /// Sets how lines longer than the screen are wrapped from now on.
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) {
+        self.wrap_mode = mode;
+    }
+    /// Runs `f` (e.g. a single `write!`) with lines wrapped according to `mode`, then goes back to the previous mode.
+    pub fn with_wrap_mode<R>(&mut self, mode: WrapMode, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.wrap_mode;
+        self.wrap_mode = mode;
+        let result = f(self);
+        self.wrap_mode = previous;
+        result
+    }
+    /// Scrolls the screen up by one row and moves to the beginning of the (now blank) bottom row.
+    pub fn new_line(&mut self) {
+        self.scroll();
+        self.buffer.flush();
+    }
+    // We move every character one row up (the top row is lost), then clear the last row.
+    fn scroll(&mut self) {
+        let height = self.buffer.height();
+        for row in 1..height {
+            for col in 0..self.buffer.width() {
+                let character = self.buffer.read_char(row, col);
+                self.buffer.write_char(row - 1, col, character);
+            }
+        }
+        self.clear_row(height - 1);
+        self.column_position = 0;
+    }
+    // Clearing a row means overwriting all of its characters with spaces
+    fn clear_row(&mut self, row: usize) {
+        let blank = ScreenChar {
+            ascii_character: b' ',
+            colour_code: self.colour_code,
+        };
+        for col in 0..self.buffer.width() {
+            self.buffer.write_char(row, col, blank);
+        }
+    }
+    /// Writes a single code page 437 byte, wrapping onto a new line at the right edge of the screen.
+    /// `\n` (new line), `\t` (next tab stop), `\r` (back to column 0), and `\x08` (backspace) are handled as controls.
+    pub fn write_byte(&mut self, byte: u8) {
+        self.put_byte(byte);
+        self.buffer.flush();
+    }
+    fn put_byte(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.scroll(),
+            b'\t' => {
+                let next_stop = (self.column_position / self.tab_width + 1) * self.tab_width;
+                self.column_position = next_stop.min(self.buffer.width());
+            }
+            b'\r' => self.column_position = 0,
+            0x08 => self.backspace(),
+            byte => self.put_glyph(byte),
+        }
+    }
+    // We print each character (i.e. a byte) with the logic below for wrapping if we reach the edge of the screen buffer.
+    // And also including the colours, and moving the column position by one each time we print a character.
+    // Control bytes are not special here, e.g. 0x09 shows ○ (see cp437.rs).
+    fn put_glyph(&mut self, byte: u8) {
+        if self.column_position >= self.buffer.width() && !self.overflow(byte) {
+            return;
+        }
+        let row = self.buffer.height() - 1;
+        let col = self.column_position;
+        let colour_code = self.colour_code;
+        // Modify the Buffer, i.e. write to it.
+        // self.buffer.chars[row][col] = ScreenChar {
+        //     ascii_character: byte,
+        //     colour_code,
+        // };
+        // However, we need volatile writes to prevent possible future compilation 
+        //      which may get rid of the step below altogether because we do not read it, and
+        //      it does not know we're writing into the VGA and not into RAM,
+        //      and the compiler may optimise it away.
+        // Below instead of directly mutating ScreenChar, we go through TextBuffer::write_char,
+        // which for the VGA Buffer is the hw::ReadWrite's (volatile) write method
+        self.buffer.write_char(row, col, ScreenChar {
+            ascii_character: byte,
+            colour_code,
+        });
+        self.column_position += 1;
+    }
+    // Backspace moves one column to the left (but not back onto the previous line), optionally erasing what is there
+    fn backspace(&mut self) {
+        if self.column_position == 0 {
+            return;
+        }
+        self.column_position -= 1;
+        if self.destructive_backspace {
+            let row = self.buffer.height() - 1;
+            self.buffer.write_char(row, self.column_position, ScreenChar {
+                ascii_character: b' ',
+                colour_code: self.colour_code,
+            });
+        }
+    }
+    // We reached the right edge and need to make room for `byte` according to the wrap mode.
+    // Returns whether `byte` should still be written.
+    fn overflow(&mut self, byte: u8) -> bool {
+        match self.wrap_mode {
+            WrapMode::Character => {
+                self.scroll();
+                true
+            }
+            WrapMode::Truncate => {
+                let (row, col) = (self.buffer.height() - 1, self.buffer.width() - 1);
+                self.buffer.write_char(row, col, ScreenChar {
+                    ascii_character: 0xaf, // »
+                    colour_code: self.colour_code,
+                });
+                false
+            }
+            WrapMode::Word => {
+                self.scroll();
+                // A space at the edge is where the line breaks, we don't start the new line with it
+                if byte == b' ' {
+                    return false;
+                }
+                self.move_last_word_down();
+                true
+            }
+        }
+    }
+    // After scrolling a full line up, we move the part after its last space (the word we are in the middle of)
+    // to the start of the new line. A word longer than the whole line has no space to break at and is simply split,
+    // and so is every word on a one-row screen, where the full line has scrolled off the top already.
+    fn move_last_word_down(&mut self) {
+        let (width, height) = (self.buffer.width(), self.buffer.height());
+        if height < 2 {
+            return;
+        }
+        let row = height - 1;
+        let start = match (0..width).rev().find(|&col| self.buffer.read_char(row - 1, col).ascii_character == b' ') {
+            Some(space) => space + 1,
+            None => return,
+        };
+        let blank = ScreenChar {
+            ascii_character: b' ',
+            colour_code: self.colour_code,
+        };
+        for col in start..width {
+            let character = self.buffer.read_char(row - 1, col);
+            self.buffer.write_char(row, col - start, character);
+            self.buffer.write_char(row - 1, col, blank);
+        }
+        self.column_position = width - start;
+    }
+    /// Writes a string, showing each character as its code page 437 glyph, or as ■ if it has none.
+    pub fn write_string(&mut self, s: &str) {
+        self.put_string(s);
+        self.buffer.flush();
+    }
+    // We need to write strings one character at a time, where a character is a Unicode code point (1 to 4 bytes in UTF-8)
+    // and each of them takes up a single cell on screen.
+    fn put_string(&mut self, s: &str) {
+        for c in s.chars() {
+            match c {
+                '\n' | '\t' | '\r' | '\x08' => self.put_byte(c as u8),
+                // Printable ASCII, and the accented/Greek/box drawing/... characters the VGA font has (see cp437.rs)
+                // For the other characters we simply print ■ (code page 437 0xfe, the 254th character), once per code point
+                c => self.put_glyph(cp437::encode(c).unwrap_or(0xfe)),
+            }
+        }
+    }
+}
+
+// Here we define Rust's formatting macros `write!` and `writeln!`, because they're nice and simple enough to implement,
+//      i.e. simple define the write_str method within the core::fmt::Write trait
+// This will allow us to use Rust's built-in write! and writeln!
+use core::fmt;
+
+impl<B: TextBuffer> fmt::Write for Writer<B> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_string(s);
+        Ok(())
+    }
+    // `write!` hands us the formatted output in many small pieces (literal text, then each argument, ...).
+    // We put all of them into the buffer first and flush only once at the end, instead of once per piece.
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        struct Unflushed<'w, B: TextBuffer>(&'w mut Writer<B>);
+        impl<'w, B: TextBuffer> fmt::Write for Unflushed<'w, B> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.put_string(s);
+                Ok(())
+            }
+        }
+        let result = fmt::write(&mut Unflushed(self), args);
+        self.buffer.flush();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBuffer;
+    use core::fmt::Write;
+
+    fn mock_writer() -> Writer<MockBuffer> {
+        Writer::new(Colour::Yellow, Colour::Black, MockBuffer::new(BUFFER_WIDTH, BUFFER_HEIGHT))
+    }
+
+    fn row_text(writer: &Writer<MockBuffer>, row: usize) -> String {
+        writer.buffer.row_text(row)
+    }
+
+    #[test]
+    fn write_string_fills_bottom_row_with_colour() {
+        let mut writer = mock_writer();
+        writer.write_string("Hello ~");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "Hello ~");
+        let c = writer.buffer.read_char(BUFFER_HEIGHT - 1, 0);
+        assert_eq!(c.colour_code, ColourCode::new(Colour::Yellow, Colour::Black));
+        assert_eq!(c.colour_code.0, 0x0e);
+    }
+
+    #[test]
+    fn unprintable_characters_become_one_square_each() {
+        let mut writer = mock_writer();
+        // "€" and "🦀" are 3 and 4 bytes in UTF-8 but only get one ■ each, as does the escape character
+        writer.write_string("€🦀\x1b!");
+        let row = &writer.buffer.chars[BUFFER_HEIGHT - 1];
+        assert!(row[..3].iter().all(|c| c.ascii_character == 0xfe));
+        assert_eq!(row[3].ascii_character, b'!');
+        assert_eq!(row[4].ascii_character, b' ');
+    }
+
+    #[test]
+    fn utf8_is_mapped_to_code_page_437() {
+        let mut writer = mock_writer();
+        writer.write_string("Wörld ┌─┐ ☺");
+        let row: Vec<u8> = writer.buffer.chars[BUFFER_HEIGHT - 1][..11].iter().map(|c| c.ascii_character).collect();
+        assert_eq!(row, [b'W', 0x94, b'r', b'l', b'd', b' ', 0xda, 0xc4, 0xbf, b' ', 0x01]);
+    }
+
+    #[test]
+    fn newline_scrolls_up() {
+        let mut writer = mock_writer();
+        write!(writer, "first\nsecond\nthird").unwrap();
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 3), "first");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 2), "second");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "third");
+    }
+
+    #[test]
+    fn long_lines_wrap() {
+        let mut writer = mock_writer();
+        for _ in 0..BUFFER_WIDTH {
+            writer.write_byte(b'a');
+        }
+        writer.write_byte(b'b');
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 2), "a".repeat(BUFFER_WIDTH));
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "b");
+    }
+
+    #[test]
+    fn top_row_is_dropped_when_screen_is_full() {
+        let mut writer = mock_writer();
+        for i in 0..=BUFFER_HEIGHT {
+            writeln!(writer, "line {}", i).unwrap();
+        }
+        assert_eq!(row_text(&writer, 0), "line 2");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 2), format!("line {}", BUFFER_HEIGHT));
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "");
+    }
+
+    #[test]
+    fn scrolling_works_on_any_buffer_size() {
+        let mut writer = Writer::new(Colour::White, Colour::Blue, MockBuffer::new(4, 2));
+        writer.write_string("abcdefg");
+        assert_eq!(row_text(&writer, 0), "abcd");
+        assert_eq!(row_text(&writer, 1), "efg");
+        writer.write_byte(b'\n');
+        assert_eq!(row_text(&writer, 0), "efg");
+        assert_eq!(row_text(&writer, 1), "");
+    }
+
+    #[test]
+    fn scrolled_in_row_is_blank_in_current_colour() {
+        let mut writer = Writer::new(Colour::White, Colour::Blue, MockBuffer::new(4, 2));
+        writer.write_byte(b'\n');
+        let blank = ScreenChar {
+            ascii_character: b' ',
+            colour_code: ColourCode::new(Colour::White, Colour::Blue),
+        };
+        assert!(writer.buffer.chars[1].iter().all(|&c| c == blank));
+    }
+
+    #[test]
+    fn writes_through_a_reference_to_the_buffer() {
+        let mut buffer = MockBuffer::new(8, 2);
+        write!(Writer::new(Colour::Green, Colour::Black, &mut buffer), "ok").unwrap();
+        assert_eq!(buffer.chars[1][0].ascii_character, b'o');
+        assert_eq!(buffer.chars[1][1].ascii_character, b'k');
+    }
+
+    #[test]
+    fn truncate_drops_the_rest_of_the_line() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(8, 3));
+        writer.set_wrap_mode(WrapMode::Truncate);
+        writer.write_string("0123456789\nnext");
+        assert_eq!(&writer.buffer.row_text(1)[..7], "0123456");
+        assert_eq!(writer.buffer.chars[1][7].ascii_character, cp437::encode('»').unwrap());
+        assert_eq!(writer.buffer.row_text(2), "next");
+    }
+
+    #[test]
+    fn word_wrap_moves_the_unfinished_word_down() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(10, 3));
+        writer.set_wrap_mode(WrapMode::Word);
+        writer.write_string("hello wonderful world");
+        assert_eq!(writer.buffer.row_text(0), "hello");
+        assert_eq!(writer.buffer.row_text(1), "wonderful");
+        assert_eq!(writer.buffer.row_text(2), "world");
+    }
+
+    #[test]
+    fn word_wrap_splits_words_longer_than_the_line() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(4, 2));
+        writer.set_wrap_mode(WrapMode::Word);
+        writer.write_string("abcdef");
+        assert_eq!(writer.buffer.row_text(0), "abcd");
+        assert_eq!(writer.buffer.row_text(1), "ef");
+    }
+
+    #[test]
+    fn word_wrap_on_a_single_row_splits_like_character_wrap() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(4, 1));
+        writer.set_wrap_mode(WrapMode::Word);
+        writer.write_string("ab cdef");
+        assert_eq!(writer.buffer.row_text(0), "def");
+    }
+
+    #[test]
+    fn wrap_mode_can_be_set_per_message() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(4, 3));
+        writer.with_wrap_mode(WrapMode::Truncate, |w| writeln!(w, "{}", 123456)).unwrap();
+        writer.write_string("abcdef");
+        assert_eq!(&writer.buffer.row_text(0)[..3], "123");
+        assert_eq!(writer.buffer.chars[0][3].ascii_character, cp437::encode('»').unwrap());
+        assert_eq!(writer.buffer.row_text(1), "abcd");
+        assert_eq!(writer.buffer.row_text(2), "ef");
+    }
+
+    #[test]
+    fn tabs_move_to_the_next_tab_stop() {
+        let mut writer = Writer::new(Colour::White, Colour::Black, MockBuffer::new(20, 2));
+        // Tabs past the last stop stop at the right edge, so "d" wraps onto the next line
+        writer.write_string("a\tb\tc\t\td");
+        assert_eq!(writer.buffer.row_text(0), "a       b       c");
+        assert_eq!(writer.buffer.row_text(1), "d");
+        writer.write_string("\n");
+        writer.set_tab_width(4);
+        writer.write_string("ab\tc\td");
+        assert_eq!(writer.buffer.row_text(1), "ab  c   d");
+    }
+
+    #[test]
+    fn carriage_return_overwrites_the_line() {
+        let mut writer = mock_writer();
+        writer.write_string("50%\r100%");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "100%");
+    }
+
+    #[test]
+    fn backspace_moves_back_and_optionally_erases() {
+        let mut writer = mock_writer();
+        writer.write_string("abc\x08\x08");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "abc");
+        writer.write_byte(b'X');
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "aXc");
+        writer.set_destructive_backspace(true);
+        writer.write_string("\x08\x08\x08\x08");
+        assert_eq!(row_text(&writer, BUFFER_HEIGHT - 1), "  c");
+        assert_eq!(writer.column_position, 0);
+    }
+
+    #[test]
+    fn control_glyphs_are_still_printable_by_name() {
+        let mut writer = mock_writer();
+        writer.write_string("○♪◘");
+        let row = &writer.buffer.chars[BUFFER_HEIGHT - 1];
+        assert_eq!([row[0].ascii_character, row[1].ascii_character, row[2].ascii_character], [0x09, 0x0d, 0x08]);
+    }
+}
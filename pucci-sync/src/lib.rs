@@ -0,0 +1,6 @@
+// Synchronisation primitives for pucci which work without a scheduler, so they can be used from interrupt handlers.
+// Like pucci-console, the crate is `no_std` everywhere except in its own tests, which run on the host
+// (with threads standing in for interrupt handlers): `cargo test-sync` (see the alias in .cargo/config.toml)
+#![cfg_attr(not(test), no_std)]
+
+pub mod mpsc;
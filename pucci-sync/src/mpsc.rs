@@ -0,0 +1,234 @@
+// A bounded lock-free multi-producer single-consumer queue, e.g. for interrupt handlers handing scancodes or
+// received bytes over to the code which processes them.
+// The queue is a ring of N slots and two ever-increasing positions: `tail`, where the next value is pushed, and
+// `head`, where the next value is popped. Position `pos` lives in slot `pos % N`, and `pos / N` is its "lap" around the ring.
+// Each slot has a stamp saying what it is waiting for on the current lap:
+//      - 2 * lap: the slot is free, i.e. waiting for a producer to write position `pos`,
+//      - 2 * lap + 1: the slot holds the value of position `pos`, i.e. waiting for the consumer to read it.
+// After reading, the consumer moves the stamp on to 2 * (lap + 1), freeing the slot for the next lap.
+// Producers claim a position by moving `tail` on with a compare-and-swap, so they never wait for each other:
+// an interrupt handler pushing while the code it interrupted is halfway through a push simply takes the next slot.
+// (Positions are 64 bits on x86_64, so they never wrap around in practice.)
+use core::cell::UnsafeCell;
+use core::hint::spin_loop;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+struct Slot<T> {
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A queue holding up to `N` values of type `T`, without allocating.
+// Typically a `static`, e.g. `static SCANCODES: Queue<u8, 128> = Queue::new();`
+pub struct Queue<T, const N: usize> {
+    slots: [Slot<T>; N],
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    consumer_taken: AtomicBool,
+}
+
+// The values move between threads (or from an interrupt handler to the interrupted code), hence `T: Send`
+unsafe impl<T: Send, const N: usize> Send for Queue<T, N> {}
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
+
+impl<T, const N: usize> Queue<T, N> {
+    // Every slot starts free for lap 0, so they can all be copies of the same constant
+    #[allow(clippy::declare_interior_mutable_const)]
+    const FREE: Slot<T> = Slot {
+        stamp: AtomicUsize::new(0),
+        value: UnsafeCell::new(MaybeUninit::uninit()),
+    };
+
+    /// Creates an empty queue. `N` must not be zero.
+    pub const fn new() -> Queue<T, N> {
+        assert!(N > 0, "a queue needs at least one slot");
+        Queue {
+            slots: [Self::FREE; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            consumer_taken: AtomicBool::new(false),
+        }
+    }
+
+    /// The number of values the queue can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Adds `value` at the end of the queue, or hands it back if the queue is full. Never blocks.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        loop {
+            let pos = self.tail.load(Ordering::Relaxed);
+            let slot = &self.slots[pos % N];
+            let free = 2 * (pos / N);
+            let stamp = slot.stamp.load(Ordering::Acquire);
+            if stamp == free {
+                if self
+                    .tail
+                    .compare_exchange_weak(pos, pos + 1, Ordering::Relaxed, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    // The position is ours alone now, and the consumer won't look at the slot until we stamp it
+                    unsafe { (*slot.value.get()).write(value) };
+                    slot.stamp.store(free + 1, Ordering::Release);
+                    return Ok(());
+                }
+            } else if stamp < free && self.tail.load(Ordering::Relaxed) == pos {
+                // The slot still holds (or is about to hold) the value from the previous lap which hasn't been popped yet
+                return Err(value);
+            }
+            // Another producer took this position first
+            spin_loop();
+        }
+    }
+
+    /// Returns the consumer end of the queue the first time it is called, and `None` afterwards.
+    pub fn consumer(&self) -> Option<Consumer<'_, T, N>> {
+        if self.consumer_taken.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some(Consumer { queue: self })
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Queue<T, N> {
+        Queue::new()
+    }
+}
+
+// Values pushed but never popped are dropped with the queue
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        let tail = *self.tail.get_mut();
+        for pos in *self.head.get_mut()..tail {
+            let slot = &mut self.slots[pos % N];
+            if *slot.stamp.get_mut() == 2 * (pos / N) + 1 {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+/// The single consumer of a `Queue`, see `Queue::consumer`.
+pub struct Consumer<'q, T, const N: usize> {
+    queue: &'q Queue<T, N>,
+}
+
+impl<'q, T, const N: usize> Consumer<'q, T, N> {
+    /// Removes the value at the front of the queue, or returns `None` if there is none yet.
+    // A producer which has claimed the front position but not written it yet also counts as "none yet",
+    // values pushed after it show up once it is done.
+    pub fn pop(&mut self) -> Option<T> {
+        let queue = self.queue;
+        let pos = queue.head.load(Ordering::Relaxed);
+        let slot = &queue.slots[pos % N];
+        let full = 2 * (pos / N) + 1;
+        if slot.stamp.load(Ordering::Acquire) != full {
+            return None;
+        }
+        let value = unsafe { (*slot.value.get()).assume_init_read() };
+        slot.stamp.store(full + 1, Ordering::Release);
+        queue.head.store(pos + 1, Ordering::Relaxed);
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn pops_in_push_order() {
+        let queue: Queue<u32, 4> = Queue::new();
+        let mut consumer = queue.consumer().unwrap();
+        // Several laps around the ring
+        for round in 0..5 {
+            for i in 0..3 {
+                queue.push(round * 10 + i).unwrap();
+            }
+            for i in 0..3 {
+                assert_eq!(consumer.pop(), Some(round * 10 + i));
+            }
+        }
+        assert_eq!(consumer.pop(), None);
+    }
+
+    #[test]
+    fn full_and_empty() {
+        let queue: Queue<u8, 2> = Queue::new();
+        let mut consumer = queue.consumer().unwrap();
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(queue.push(1), Ok(()));
+        assert_eq!(queue.push(2), Ok(()));
+        assert_eq!(queue.push(3), Err(3));
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(queue.push(3), Ok(()));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(queue.capacity(), 2);
+    }
+
+    #[test]
+    fn only_one_consumer() {
+        let queue: Queue<u8, 1> = Queue::new();
+        assert!(queue.consumer().is_some());
+        assert!(queue.consumer().is_none());
+    }
+
+    #[test]
+    fn unpopped_values_are_dropped() {
+        let value = Arc::new(());
+        {
+            let queue: Queue<Arc<()>, 4> = Queue::new();
+            let mut consumer = queue.consumer().unwrap();
+            for _ in 0..3 {
+                queue.push(value.clone()).unwrap();
+            }
+            drop(consumer.pop());
+            assert_eq!(Arc::strong_count(&value), 3);
+        }
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn racing_producers() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+        let queue: Queue<(usize, usize), 16> = Queue::new();
+        let mut consumer = queue.consumer().unwrap();
+        thread::scope(|s| {
+            for producer in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        let mut value = (producer, i);
+                        while let Err(rejected) = queue.push(value) {
+                            value = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+            // Every value arrives exactly once, and each producer's values arrive in the order it pushed them
+            let mut next = [0; PRODUCERS];
+            let mut received = 0;
+            while received < PRODUCERS * PER_PRODUCER {
+                match consumer.pop() {
+                    Some((producer, i)) => {
+                        assert_eq!(i, next[producer]);
+                        next[producer] += 1;
+                        received += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+            assert_eq!(next, [PER_PRODUCER; PRODUCERS]);
+        });
+        assert_eq!(consumer.pop(), None);
+    }
+}
@@ -0,0 +1,16 @@
+// Compile-time kernel configuration.
+// Whole subsystems are switched on and off with Cargo features (see `[features]` in Cargo.toml), e.g.
+// `cargo build --no-default-features` for a kernel which prints nothing, and the knobs within them are the constants below.
+// We use `cfg!` rather than `#[cfg]` so that every configuration still type-checks all of the code.
+use pucci_console::vga_buffer::{Colour, WrapMode};
+
+// Print to the VGA text buffer (feature `vga`)
+pub const VGA: bool = cfg!(feature = "vga");
+// Draw the full-screen panic report (feature `panic-screen`, which needs `vga`)
+pub const PANIC_SCREEN: bool = cfg!(feature = "panic-screen");
+
+// Colours of the kernel's console output
+pub const CONSOLE_FOREGROUND: Colour = Colour::Red;
+pub const CONSOLE_BACKGROUND: Colour = Colour::White;
+// What the console does with lines longer than the screen (can also be changed per message with `Writer::with_wrap_mode`)
+pub const CONSOLE_WRAP_MODE: WrapMode = WrapMode::Character;
@@ -3,12 +3,28 @@
 
 use core::panic::PanicInfo;
 
-mod vga_buffer;
+use pucci_console::panic_screen::{self, PanicReport, Registers};
+use pucci_console::shadow::ShadowBuffer;
+use pucci_console::vga_buffer::{self, Writer};
+
+mod config;
 
 // Panic handler
+// We take over the whole screen and show the panic message, location, and a few registers (see pucci-console/src/panic_screen.rs).
 #[cfg(not(test))] // This line is used to disable rust-analyzer from winging duplicate panic definition as it is unable to see that we are not including std!
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
+fn panic(info: &PanicInfo) -> ! {
+	if config::PANIC_SCREEN {
+		// Capture the registers first, before drawing clobbers them (the kernel runs in ring 0, so reading them is fine)
+		let registers = unsafe { Registers::capture() };
+		// Whatever Writer was printing when we panicked will never run again, so it is fine to steal its buffer
+		let buffer = unsafe { vga_buffer::steal_vga_buffer() };
+		panic_screen::draw(buffer, &PanicReport {
+			message: &info.message(),
+			location: info.location().map(|l| (l.file(), l.line(), l.column())),
+			registers,
+		});
+	}
 	loop {}
 }
 
@@ -30,11 +46,28 @@ pub extern "C" fn _start() -> ! {
 	// 		*vga_buffer.offset(i as isize * 2 + 1) = 0xb;
 	// 	}
 	// }
-	vga_buffer::print_someshit();
+	if config::VGA {
+		print_someshit();
+	}
 
 	loop {}
 }
 
+// Test screen writing function
+fn print_someshit() {
+	use core::fmt::Write; // Use Rust's built-in formatting macros (`write!` and `writeln!`) which we implemented for our Writer struct.
+	// The bootloader identity-maps the VGA text memory at 0xb8000, so the buffer is really there
+	let buffer = unsafe { vga_buffer::take_vga_buffer() }.expect("Error: the VGA buffer has already been taken!");
+	// Writing through a shadow copy means each write! reaches VGA memory in one go, and only the cells which changed
+	let mut writer = Writer::new(config::CONSOLE_FOREGROUND, config::CONSOLE_BACKGROUND, ShadowBuffer::new(buffer));
+	writer.set_wrap_mode(config::CONSOLE_WRAP_MODE);
+	writer.write_byte(b'H');
+	writer.write_string("ello ");
+	// writer.write_string("Wörld!");
+	// Using Rust's built-in write! macro after implementing `write_str` method for our Writer struct
+	write!(writer, "World!\nNice numbers in my opinion are {} and {}.", 42.00000000000001, 789.0/123.0).expect("Error: something went wrong with writing our characters into VGA memory!");
+}
+
 // # Bare-bones compilation
 // `cargo rustc -- -C link-arg=-nostartfiles`
 // For details see [here](https://os.phil-opp.com/freestanding-rust-binary/).
@@ -87,6 +120,10 @@ pub extern "C" fn _start() -> ! {
 // |15		| Blink					|
 // 
 // Run: `cargo run` 
+//
+// # Kernel configuration
+// Subsystems are Cargo features (`vga` and `panic-screen`, both on by default) and their settings are constants in [`src/config.rs`](src/config.rs).
+// For example, `cargo run --no-default-features` boots a kernel which prints nothing and hangs silently on panic.
 // 
 // 
 // 